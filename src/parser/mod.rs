@@ -0,0 +1,437 @@
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Stdio};
+use std::sync::{Arc, Mutex};
+
+use crate::shell::command::BuildInCmd;
+use crate::shell::search_in_path;
+
+/// 一条重定向：把某个 fd 接到文件或行内文本上。
+#[derive(Clone)]
+pub enum Redirection {
+    /// `> path` / `>> path`：把 `fd` 写到文件，`append` 为真时以追加方式打开。
+    Output { fd: i32, path: String, append: bool },
+    /// `< path`：把 `fd`（通常是 0）从文件读入。
+    Input { fd: i32, path: String },
+    /// `2>&1` 这类 fd 复制：把 `fd` 指向 `target_fd`。
+    Dup { fd: i32, target_fd: i32 },
+    /// `2>&-` 这类 fd 关闭：直接关掉 `fd`。
+    Close { fd: i32 },
+    /// `<<TAG` here-doc：把收集到的文本作为 `fd`（通常是 0）的输入。
+    HereDoc { fd: i32, body: String },
+}
+
+/// 流水线中的单条命令：命令名、参数以及附带的重定向列表。
+#[derive(Clone)]
+pub struct Command {
+    pub name: String,
+    pub args: Vec<String>,
+    pub redirections: Vec<Redirection>,
+}
+
+impl Command {
+    fn new(name: String) -> Command {
+        Command {
+            name,
+            args: Vec::new(),
+            redirections: Vec::new(),
+        }
+    }
+}
+
+/// 由 `|` 连接、可带尾随 `&` 的一组命令。
+pub struct Pipeline {
+    commands: Vec<Command>,
+    backend: bool,
+}
+
+impl Pipeline {
+    pub fn backend(&self) -> bool {
+        self.backend
+    }
+
+    /// 执行整条流水线，返回外部命令对应的子进程句柄。
+    ///
+    /// 内建命令在本进程内同步执行、不产生子进程；外部命令通过 `PATH` 解析后
+    /// 启动。命令之间用匿名管道串联，每条命令自己的显式重定向优先级高于管道
+    /// （例如非末端命令的 `>` 会覆盖它本应写入管道的 stdout）。
+    pub fn execute(
+        &self,
+        map: Option<Arc<Mutex<HashMap<String, BuildInCmd>>>>,
+    ) -> Vec<Child> {
+        let mut children: Vec<Child> = Vec::new();
+        let mut prev_read: Option<Stdio> = None;
+        // 进程组组长的 pid：第一条外部命令自建新组，其余命令加入该组。
+        let mut pgid: Option<i32> = None;
+        let len = self.commands.len();
+
+        for (index, command) in self.commands.iter().enumerate() {
+            // 内建命令直接在 shell 进程里跑，不纳入子进程管理
+            if let Some(map) = map.as_ref() {
+                if let Some(cmd) = map.lock().unwrap().get(command.name.as_str()) {
+                    cmd.execute(command.args.clone());
+                    prev_read = None;
+                    continue;
+                }
+            }
+
+            let program = match search_in_path(&command.name) {
+                Some(path) => path,
+                None => command.name.clone(),
+            };
+
+            let mut process = Command::build(&program, command);
+
+            // 进程组必须在 exec 之前、于子进程内设置：spawn() 要等子进程 execve
+            // 之后才返回，父进程此时再 setpgid 会得到 EACCES。`process_group(0)`
+            // 让首个子进程自成新组（pgid==其 pid），后续命令显式加入该组。
+            process.process_group(pgid.unwrap_or(0));
+
+            // stdin：优先用上一条命令的管道读端
+            if let Some(read) = prev_read.take() {
+                process.stdin(read);
+            }
+
+            // 只要不是最后一条命令，就为下一条准备一个管道写端
+            let has_next = index + 1 < len;
+            if has_next {
+                process.stdout(Stdio::piped());
+            }
+
+            match process.spawn() {
+                Ok(mut child) => {
+                    if pgid.is_none() {
+                        pgid = Some(child.id() as i32);
+                    }
+                    if has_next {
+                        prev_read = child.stdout.take().map(Stdio::from);
+                    }
+                    children.push(child);
+                }
+                Err(e) => {
+                    println!("{}: {}", command.name, e);
+                    prev_read = None;
+                }
+            }
+        }
+
+        children
+    }
+}
+
+impl Command {
+    /// 构造一个 `std::process::Command`，并按 AST 里的重定向设置 pre_exec 钩子。
+    fn build(program: &str, command: &Command) -> Command2 {
+        let mut process = Command2::new(program);
+        process.args(&command.args);
+
+        let redirs = command.redirections.clone();
+        // 在子进程中、exec 之前完成全部 dup2，这样显式重定向会覆盖管道设置
+        unsafe {
+            process.pre_exec(move || {
+                // shell 自身把这些终端信号设为 SIG_IGN，而 SIG_IGN 会经 fork+exec
+                // 继承下来；若不恢复默认处置，前台子进程就永远收不到 Ctrl-C/Ctrl-\
+                // /Ctrl-Z，作业控制形同虚设。exec 前逐一复位为 SIG_DFL。
+                reset_signals_to_default();
+                for redir in &redirs {
+                    apply_redirection(redir)?;
+                }
+                Ok(())
+            });
+        }
+        process
+    }
+}
+
+// 为可读性起个别名：Command 既是我们的 AST 节点，也要用到标准库的 Command。
+type Command2 = std::process::Command;
+
+/// 在子进程里落实一条重定向：打开目标并 `dup2` 到对应 fd。
+fn apply_redirection(redir: &Redirection) -> std::io::Result<()> {
+    match redir {
+        Redirection::Output { fd, path, append } => {
+            let file = OpenOptions::new()
+                .write(true)
+                .create(true)
+                .append(*append)
+                .truncate(!*append)
+                .open(path)?;
+            dup2(file.as_raw_fd(), *fd)?;
+            std::mem::forget(file);
+        }
+        Redirection::Input { fd, path } => {
+            let file = OpenOptions::new().read(true).open(path)?;
+            dup2(file.as_raw_fd(), *fd)?;
+            std::mem::forget(file);
+        }
+        Redirection::Dup { fd, target_fd } => {
+            dup2(*target_fd, *fd)?;
+        }
+        Redirection::Close { fd } => {
+            unsafe { libc::close(*fd) };
+        }
+        Redirection::HereDoc { fd, body } => {
+            // here-doc 正文先落到一个匿名内存文件里，再把该 fd 接到目标 fd。
+            // 不能往无人读的管道里同步灌正文——超过管道缓冲区（约 64 KiB）就会
+            // 在 `write` 上死锁，因为此刻子进程是唯一的进程。
+            let mem_fd = unsafe {
+                libc::memfd_create(b"heredoc\0".as_ptr() as *const libc::c_char, 0)
+            };
+            if mem_fd < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            let bytes = body.as_bytes();
+            let mut written = 0usize;
+            while written < bytes.len() {
+                let n = unsafe {
+                    libc::write(
+                        mem_fd,
+                        bytes[written..].as_ptr() as *const libc::c_void,
+                        bytes.len() - written,
+                    )
+                };
+                if n <= 0 {
+                    unsafe { libc::close(mem_fd) };
+                    return Err(std::io::Error::last_os_error());
+                }
+                written += n as usize;
+            }
+            // 倒回开头，让子进程从正文首字节开始读
+            if unsafe { libc::lseek(mem_fd, 0, libc::SEEK_SET) } < 0 {
+                unsafe { libc::close(mem_fd) };
+                return Err(std::io::Error::last_os_error());
+            }
+            dup2(mem_fd, *fd)?;
+            unsafe { libc::close(mem_fd) };
+        }
+    }
+    Ok(())
+}
+
+/// 把作业控制相关的终端信号恢复为默认处置，供子进程在 exec 前调用。
+fn reset_signals_to_default() {
+    for sig in [
+        libc::SIGINT,
+        libc::SIGQUIT,
+        libc::SIGTSTP,
+        libc::SIGTTOU,
+        libc::SIGTTIN,
+    ] {
+        unsafe {
+            libc::signal(sig, libc::SIG_DFL);
+        }
+    }
+}
+
+fn dup2(from: i32, to: i32) -> std::io::Result<()> {
+    if unsafe { libc::dup2(from, to) } == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+pub struct Parser;
+
+impl Parser {
+    /// 把一整行输入解析成若干条流水线（按 `;` 拆分，当前实现只关心单行）。
+    pub fn parse(input: &str) -> Result<Vec<Pipeline>, String> {
+        let mut pipelines = Vec::new();
+        for segment in input.split(';') {
+            let segment = segment.trim();
+            if segment.is_empty() {
+                continue;
+            }
+            pipelines.push(Self::parse_pipeline(segment)?);
+        }
+        Ok(pipelines)
+    }
+
+    fn parse_pipeline(segment: &str) -> Result<Pipeline, String> {
+        let mut tokens = tokenize(segment);
+        let backend = matches!(tokens.last().map(String::as_str), Some("&"));
+        if backend {
+            tokens.pop();
+        }
+
+        let mut commands = Vec::new();
+        let mut current: Option<Command> = None;
+        let mut iter = tokens.into_iter().peekable();
+
+        while let Some(token) = iter.next() {
+            match token.as_str() {
+                "|" => {
+                    if let Some(cmd) = current.take() {
+                        commands.push(cmd);
+                    }
+                }
+                t if t == ">"
+                    || t == ">>"
+                    || t == "<"
+                    || t == "2>"
+                    || t == "<<"
+                    || t.starts_with("2>&") =>
+                {
+                    let cmd = current
+                        .as_mut()
+                        .ok_or_else(|| "syntax error near redirection".to_string())?;
+                    match token.as_str() {
+                        t if t.starts_with("2>&") => {
+                            // `2>&<digit>` 复制 fd，`2>&-` 关闭 fd
+                            let target = &t[3..];
+                            let redir = if target == "-" {
+                                Redirection::Close { fd: 2 }
+                            } else {
+                                let target_fd: i32 = target
+                                    .parse()
+                                    .map_err(|_| format!("invalid redirection: {}", t))?;
+                                Redirection::Dup { fd: 2, target_fd }
+                            };
+                            cmd.redirections.push(redir);
+                        }
+                        "<<" => {
+                            let tag = iter.next().ok_or("missing here-doc delimiter")?;
+                            // 正文在 here-doc 收集阶段以 \0<body> 形式附在分隔符后
+                            let (_tag, body) = split_heredoc(&tag);
+                            cmd.redirections.push(Redirection::HereDoc { fd: 0, body });
+                        }
+                        op => {
+                            let target = iter.next().ok_or("missing redirection target")?;
+                            let redir = match op {
+                                ">" => Redirection::Output { fd: 1, path: target, append: false },
+                                ">>" => Redirection::Output { fd: 1, path: target, append: true },
+                                "2>" => Redirection::Output { fd: 2, path: target, append: false },
+                                "<" => Redirection::Input { fd: 0, path: target },
+                                _ => unreachable!(),
+                            };
+                            cmd.redirections.push(redir);
+                        }
+                    }
+                }
+                word => match current.as_mut() {
+                    Some(cmd) => cmd.args.push(word.to_string()),
+                    None => current = Some(Command::new(word.to_string())),
+                },
+            }
+        }
+
+        if let Some(cmd) = current.take() {
+            commands.push(cmd);
+        }
+
+        Ok(Pipeline { commands, backend })
+    }
+}
+
+/// here-doc 收集阶段把正文十六进制编码后以 `\0` 分隔拼在分隔符 token 之后，
+/// 这里拆开并解码回原始正文。
+fn split_heredoc(token: &str) -> (String, String) {
+    match token.split_once('\0') {
+        Some((tag, hex)) => (tag.to_string(), hex_decode(hex)),
+        None => (token.to_string(), String::new()),
+    }
+}
+
+fn hex_decode(hex: &str) -> String {
+    let bytes: Vec<u8> = (0..hex.len())
+        .step_by(2)
+        .filter_map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// 把一行切成词与操作符 token，识别引号、管道、后台符与各重定向操作符。
+fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut stack = String::new();
+    let mut left_quote = ' ';
+    let mut chars = input.chars().peekable();
+
+    let flush = |stack: &mut String, tokens: &mut Vec<String>| {
+        if !stack.is_empty() {
+            tokens.push(std::mem::take(stack));
+        }
+    };
+
+    while let Some(ch) = chars.next() {
+        if left_quote != ' ' {
+            if ch == left_quote {
+                left_quote = ' ';
+            }
+            stack.push(ch);
+            continue;
+        }
+        match ch {
+            '\'' | '\"' => {
+                left_quote = ch;
+                stack.push(ch);
+            }
+            ' ' | '\t' => flush(&mut stack, &mut tokens),
+            '|' => {
+                flush(&mut stack, &mut tokens);
+                tokens.push("|".to_string());
+            }
+            // 仅在词边界上把 `<`/`<<` 当作操作符，词内部的 `<`（如算术
+            // `$((1<<2))`）按普通字符处理；引号内的由上面的引号分支兜住。
+            '<' if stack.is_empty() => {
+                if chars.peek() == Some(&'<') {
+                    chars.next();
+                    tokens.push("<<".to_string());
+                } else {
+                    tokens.push("<".to_string());
+                }
+            }
+            '&' => {
+                flush(&mut stack, &mut tokens);
+                tokens.push("&".to_string());
+            }
+            '>' => {
+                flush(&mut stack, &mut tokens);
+                if chars.peek() == Some(&'>') {
+                    chars.next();
+                    tokens.push(">>".to_string());
+                } else {
+                    tokens.push(">".to_string());
+                }
+            }
+            '2' if chars.peek() == Some(&'>') && stack.is_empty() => {
+                chars.next();
+                if chars.peek() == Some(&'&') {
+                    chars.next();
+                    // 解析复制目标：`&<digit>` 或 `&-`，不能盲目当作 `1`
+                    match chars.peek() {
+                        Some('-') => {
+                            chars.next();
+                            tokens.push("2>&-".to_string());
+                        }
+                        Some(d) if d.is_ascii_digit() => {
+                            let d = *d;
+                            chars.next();
+                            tokens.push(format!("2>&{}", d));
+                        }
+                        _ => tokens.push("2>&".to_string()),
+                    }
+                } else {
+                    tokens.push("2>".to_string());
+                }
+            }
+            _ => stack.push(ch),
+        }
+    }
+    flush(&mut stack, &mut tokens);
+
+    // 去掉词里用于分段的引号
+    tokens
+        .into_iter()
+        .map(|t| {
+            if matches!(t.as_str(), "|" | "&" | ">" | ">>" | "<" | "2>" | "<<")
+                || t.starts_with("2>&")
+            {
+                t
+            } else {
+                t.replace('\'', "").replace('\"', "")
+            }
+        })
+        .collect()
+}
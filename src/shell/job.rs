@@ -0,0 +1,158 @@
+use std::fmt;
+
+use libc::{self, pid_t};
+
+/// 前台/后台任务的运行状态。
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum JobState {
+    Running,
+    Stopped,
+    Done,
+}
+
+impl fmt::Display for JobState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            JobState::Running => "Running",
+            JobState::Stopped => "Stopped",
+            JobState::Done => "Done",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// 一个受作业控制管理的任务，整体对应一个独立的进程组。
+///
+/// `pgid` 既是组号也是组长子进程的 pid；`pids` 记录组内所有子进程，
+/// 以便在它们全部退出后把整个作业标记为 `Done`。
+pub struct Job {
+    /// 作业号，即 `%n`、`fg %n` 中的 n。
+    pub id: usize,
+    /// 进程组号。
+    pub pgid: pid_t,
+    /// 组内全部子进程的 pid。
+    pub pids: Vec<pid_t>,
+    /// 原始命令行，用于 `jobs` 展示。
+    pub command: String,
+    /// 当前状态。
+    pub state: JobState,
+}
+
+impl Job {
+    pub fn new(id: usize, pgid: pid_t, pids: Vec<pid_t>, command: String, state: JobState) -> Job {
+        Job {
+            id,
+            pgid,
+            pids,
+            command,
+            state,
+        }
+    }
+
+    /// 给整个进程组发送信号。
+    pub fn signal(&self, sig: i32) {
+        unsafe {
+            libc::killpg(self.pgid, sig);
+        }
+    }
+
+    /// 轮询组内子进程，返回该作业是否已经全部结束。
+    ///
+    /// 使用 `WNOHANG | WUNTRACED | WCONTINUED` 非阻塞地收集状态变化，
+    /// 据此在后台推进 `Running`/`Stopped`/`Done`。
+    pub fn poll(&mut self) -> bool {
+        self.pids.retain(|&pid| {
+            let mut status: i32 = 0;
+            let r = unsafe {
+                libc::waitpid(
+                    pid,
+                    &mut status as *mut i32,
+                    libc::WNOHANG | libc::WUNTRACED | libc::WCONTINUED,
+                )
+            };
+            if r == pid {
+                if unsafe { libc::WIFEXITED(status) } || unsafe { libc::WIFSIGNALED(status) } {
+                    return false;
+                }
+                if unsafe { libc::WIFSTOPPED(status) } {
+                    self.state = JobState::Stopped;
+                } else if unsafe { libc::WIFCONTINUED(status) } {
+                    self.state = JobState::Running;
+                }
+            }
+            true
+        });
+
+        if self.pids.is_empty() {
+            self.state = JobState::Done;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// `Shell` 持有的作业表。
+pub struct JobTable {
+    jobs: Vec<Job>,
+    next_id: usize,
+}
+
+impl JobTable {
+    pub fn new() -> JobTable {
+        JobTable {
+            jobs: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    /// 登记一个新作业，返回分配到的作业号。
+    pub fn insert(&mut self, pgid: pid_t, pids: Vec<pid_t>, command: String, state: JobState) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.jobs.push(Job::new(id, pgid, pids, command, state));
+        id
+    }
+
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut Job> {
+        self.jobs.iter_mut().find(|job| job.id == id)
+    }
+
+    /// 若未显式给出作业号，`fg`/`bg` 默认作用于最近的作业。
+    pub fn current_id(&self) -> Option<usize> {
+        self.jobs.last().map(|job| job.id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Job> {
+        self.jobs.iter()
+    }
+
+    /// 轮询所有后台作业，打印已结束者的完成通知并将其从表中移除。
+    ///
+    /// 在每次显示提示符之前调用，模拟交互式 shell 的作业回收时机。
+    pub fn notify_finished(&mut self) {
+        let mut done = Vec::new();
+        for job in self.jobs.iter_mut() {
+            if job.poll() {
+                done.push(job.id);
+            }
+        }
+        self.jobs.retain(|job| {
+            if done.contains(&job.id) {
+                println!("[{}]\tDone\t{}", job.id, job.command);
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    /// 从作业表中移除指定作业（被 `fg` 重新拉回前台并结束后调用）。
+    pub fn remove(&mut self, id: usize) {
+        self.jobs.retain(|job| job.id != id);
+    }
+}
@@ -11,28 +11,89 @@ use std::{
 use crate::{
     env::EnvManager,
     keycode::{FunctionKeySuffix, SpecialKeycode},
-    parser::{Parser, Pipeline},
+    parser::Parser,
 };
 
 use colored::Colorize;
 use command::BuildInCmd;
+use job::{JobState, JobTable};
 use printer::Printer;
-use thread_manager::ThreadManager;
 
 mod printer;
 
-mod thread_manager;
+mod job;
 
 pub mod command;
 
 const DEFAULT_HISTORY_COMMANDS_PATH: &str = "/history_commands.txt";
 
+/// 行内编辑缓冲的删除/粘贴操作，统一收拢在 `Printer` 上，让光标移动与重绘
+/// 的簿记只有一处。各删除操作返回被删掉的字节，由调用方决定是否存入 kill-ring。
+impl Printer {
+    /// 删除光标左边的一个单词：先越过前导空格，再删到空白或引号边界。
+    pub fn erase_word(&mut self) -> Vec<u8> {
+        let cursor = self.cursor;
+        if cursor == 0 {
+            return Vec::new();
+        }
+        let buf = self.buf.borrow().clone();
+        let is_boundary = |b: u8| b == b' ' || b == b'\t' || b == b'\'' || b == b'\"';
+        let mut start = cursor;
+        while start > 0 && buf[start - 1] == b' ' {
+            start -= 1;
+        }
+        while start > 0 && !is_boundary(buf[start - 1]) {
+            start -= 1;
+        }
+        let n = cursor - start;
+        if n == 0 {
+            return Vec::new();
+        }
+        let killed = buf[start..cursor].to_vec();
+        self.cursor_left(n);
+        self.delete(n);
+        killed
+    }
+
+    /// 删除从行首到光标之间的内容。
+    pub fn kill_to_start(&mut self) -> Vec<u8> {
+        let cursor = self.cursor;
+        if cursor == 0 {
+            return Vec::new();
+        }
+        let killed = self.buf.borrow()[..cursor].to_vec();
+        self.cursor_left(cursor);
+        self.delete(cursor);
+        killed
+    }
+
+    /// 删除从光标到行尾的内容。
+    pub fn kill_to_end(&mut self) -> Vec<u8> {
+        let cursor = self.cursor;
+        let len = self.buf.borrow().len();
+        if cursor >= len {
+            return Vec::new();
+        }
+        let killed = self.buf.borrow()[cursor..].to_vec();
+        self.delete(len - cursor);
+        killed
+    }
+
+    /// 把给定文本插入到光标处（Ctrl-Y 粘贴 kill-ring）。
+    pub fn yank(&mut self, text: &[u8]) {
+        if !text.is_empty() {
+            self.insert(text);
+        }
+    }
+}
+
 #[allow(dead_code)]
 pub struct Shell {
     history_commands: Vec<Rc<RefCell<Vec<u8>>>>,
     history_path: String,
     printer: Printer,
-    backend_thread: ThreadManager<(String, Vec<Pipeline>), Child>,
+    jobs: JobTable,
+    kill_ring: Vec<u8>,
 }
 
 impl Shell {
@@ -45,37 +106,26 @@ impl Shell {
             history_commands: Vec::new(),
             history_path: DEFAULT_HISTORY_COMMANDS_PATH.to_string(),
             printer: Printer::new(&Rc::new(RefCell::new(Vec::new()))),
-            backend_thread: Self::create_backend_thread(),
+            jobs: JobTable::new(),
+            kill_ring: Vec::new(),
         };
         shell.read_commands();
         shell
     }
 
-    fn create_backend_thread() -> ThreadManager<(String, Vec<Pipeline>), Child> {
-        ThreadManager::new(|| {
-            let (p_s, c_r) = std::sync::mpsc::channel::<(String, Vec<Pipeline>)>();
-            let (c_s, p_r) = std::sync::mpsc::channel::<Child>();
-            let map = BuildInCmd::map();
-            let func = move || loop {
-                if let Ok((dir, pipelines)) = c_r.recv() {
-                    std::env::set_current_dir(dir).expect("set current dir failed");
-                    for pipeline in pipelines {
-                        for child in pipeline.execute(map.clone()) {
-                            let _ = c_s.send(child);
-                        }
-                    }
-                };
-            };
-            (p_s, p_r, func)
-        })
-    }
-
     pub fn exec(&mut self) {
         // 设置前台进程组
         unsafe {
             libc::ioctl(libc::STDIN_FILENO, libc::TIOCSCTTY, 0);
             libc::tcsetpgrp(libc::STDIN_FILENO, std::process::id() as i32);
 
+            // 作为作业控制 shell，自身忽略这些终端信号：Ctrl-C/Ctrl-\ 不应打断
+            // shell，Ctrl-Z 只针对前台作业，而忽略 SIGTTOU 才能在后台安全地调用
+            // tcsetpgrp 来回收终端。前台子进程由内核按控制终端直接投递信号。
+            libc::signal(libc::SIGINT, libc::SIG_IGN);
+            libc::signal(libc::SIGQUIT, libc::SIG_IGN);
+            libc::signal(libc::SIGTSTP, libc::SIG_IGN);
+            libc::signal(libc::SIGTTOU, libc::SIG_IGN);
         };
 
         // 开启终端raw模式
@@ -83,6 +133,11 @@ impl Shell {
 
         // 循环读取一行
         loop {
+            // 在显示提示符前回收已结束的后台作业并打印完成通知。
+            // 先退出 raw 模式，否则 `\n` 不带 `\r`，通知会呈阶梯状错位。
+            crossterm::terminal::disable_raw_mode().ok();
+            self.jobs.notify_finished();
+            crossterm::terminal::enable_raw_mode().expect("failed to enable raw mode");
             self.printer.init_before_readline();
             // 读取一行
             if self.readline() == 0 {
@@ -116,8 +171,27 @@ impl Shell {
 
     fn exec_commands_in_line(&mut self, command_bytes: &Vec<u8>) {
         // 解析命令
-        let input_command = String::from_utf8(command_bytes.clone()).unwrap();
-        let pipelines = Parser::parse(&input_command).unwrap();
+        let mut input_command = String::from_utf8(command_bytes.clone()).unwrap();
+
+        // 若命令里含真正的 `<<TAG` 操作符，先把 here-doc 正文逐行读进来。
+        // 引号内或词内部（如 `echo $((1<<2))`）的 `<<` 不算数。
+        if Self::find_heredoc_op(&input_command, 0).is_some() {
+            input_command = self.collect_heredocs(input_command);
+        }
+
+        // jobs/fg/bg 依赖 shell 自身的作业表，只能作为 shell 内建命令处理
+        if self.exec_job_builtin(&input_command) {
+            return;
+        }
+
+        let pipelines = match Parser::parse(&input_command) {
+            Ok(pipelines) => pipelines,
+            Err(e) => {
+                // 重定向语法错误只针对本行，打印提示后回到提示符而非终止 shell
+                println!("{}", e);
+                return;
+            }
+        };
 
         let mut foreground_pipelines = Vec::new();
         let mut backend_pipelines = Vec::new();
@@ -130,18 +204,18 @@ impl Shell {
             }
         }
 
-        // 后台pipeline发送给子线程执行
-        let _ = self
-            .backend_thread
-            .send((EnvManager::current_dir(), backend_pipelines));
-
         crossterm::terminal::disable_raw_mode().expect("failed to disable raw mode");
 
+        // 后台 pipeline 在本进程里启动，纳入作业表以便 jobs/fg/bg 管理与回收
+        for pipeline in &backend_pipelines {
+            let children = pipeline.execute(BuildInCmd::map().clone());
+            self.start_background(children, &input_command);
+        }
+
         // 顺序执行所有前台pipeline
         for pipeline in &foreground_pipelines {
-            for mut child in pipeline.execute(BuildInCmd::map().clone()) {
-                let _ = child.wait();
-            }
+            let children = pipeline.execute(BuildInCmd::map().clone());
+            self.wait_foreground(children, &input_command);
         }
 
         crossterm::terminal::enable_raw_mode().expect("failed to enable raw mode");
@@ -149,6 +223,243 @@ impl Shell {
         foreground_pipelines.clear();
     }
 
+    /// 把终端控制权交给一条前台流水线的进程组（组已在 `Pipeline::execute` 中建立）后等待它结束。
+    ///
+    /// 若进程组被 `SIGTSTP`（Ctrl-Z）挂起，则把它记入作业表并立即返回，
+    /// 让用户回到提示符；正常退出的作业不会进入作业表。
+    fn wait_foreground(&mut self, children: Vec<Child>, command: &str) {
+        if children.is_empty() {
+            return;
+        }
+
+        let shell_pgid = std::process::id() as i32;
+        let pids: Vec<libc::pid_t> = children.iter().map(|c| c.id() as libc::pid_t).collect();
+        let pgid = pids[0];
+
+        // 子进程已在 exec 前自建进程组（见 Pipeline::execute），这里只需把终端交给它
+        unsafe {
+            libc::tcsetpgrp(libc::STDIN_FILENO, pgid);
+        }
+
+        let mut stopped = false;
+        let mut alive = pids.clone();
+        for &pid in &pids {
+            let mut status: i32 = 0;
+            let r = unsafe { libc::waitpid(pid, &mut status as *mut i32, libc::WUNTRACED) };
+            if r == pid {
+                if unsafe { libc::WIFSTOPPED(status) } {
+                    stopped = true;
+                    break;
+                }
+                alive.retain(|&p| p != pid);
+            }
+        }
+
+        // 无论作业停止与否，都把终端收回给 shell 自身
+        unsafe {
+            libc::tcsetpgrp(libc::STDIN_FILENO, shell_pgid);
+        }
+
+        if stopped {
+            let id = self
+                .jobs
+                .insert(pgid, alive, command.trim().to_string(), JobState::Stopped);
+            println!("[{}]\tStopped\t{}", id, command.trim());
+        }
+    }
+
+    /// 登记一条已启动的后台流水线（其进程组已在 `Pipeline::execute` 中建立，不交出终端），
+    /// 以 `Running` 状态登记进作业表，并打印 `[n] pgid` 提示。
+    fn start_background(&mut self, children: Vec<Child>, command: &str) {
+        if children.is_empty() {
+            return;
+        }
+
+        let pids: Vec<libc::pid_t> = children.iter().map(|c| c.id() as libc::pid_t).collect();
+        // 子进程已在 exec 前自建进程组（见 Pipeline::execute），组长即首个子进程
+        let pgid = pids[0];
+
+        let id = self
+            .jobs
+            .insert(pgid, pids, command.trim().to_string(), JobState::Running);
+        println!("[{}]\t{}", id, pgid);
+    }
+
+    /// 在 `from` 之后查找下一个真正作为 here-doc 操作符的 `<<` 的字节下标。
+    ///
+    /// 只认不在引号内、且处于词边界（前面是空白或行首）的 `<<`；引号里的
+    /// `"a<<b"` 和词内部的 `$((1<<2))` 都会被跳过，以免误入 here-doc 收集。
+    fn find_heredoc_op(s: &str, from: usize) -> Option<usize> {
+        let bytes = s.as_bytes();
+        let mut quote = 0u8;
+        let mut i = 0;
+        while i < bytes.len() {
+            let c = bytes[i];
+            if quote != 0 {
+                if c == quote {
+                    quote = 0;
+                }
+                i += 1;
+                continue;
+            }
+            match c {
+                b'\'' | b'\"' => {
+                    quote = c;
+                    i += 1;
+                }
+                b'<' if i + 1 < bytes.len() && bytes[i + 1] == b'<' => {
+                    let boundary = i == 0 || matches!(bytes[i - 1], b' ' | b'\t');
+                    if boundary && i >= from {
+                        return Some(i);
+                    }
+                    i += 2;
+                }
+                _ => i += 1,
+            }
+        }
+        None
+    }
+
+    /// 收集命令行里每个 `<<TAG` 的 here-doc 正文。
+    ///
+    /// 逐行读取直到遇到单独成行的 `TAG`，再把正文以十六进制编码附在分隔符
+    /// 之后（`<<TAG` → `<<TAG\0<hex>`），这样正文里的空格与换行不会干扰
+    /// 解析器的分词，解析器再把它解码回来。
+    fn collect_heredocs(&mut self, command: String) -> String {
+        let mut result = command.clone();
+        let mut search_from = 0;
+        while let Some(op) = Self::find_heredoc_op(&result, search_from) {
+            let after = &result[op + 2..];
+            let tag: String = after
+                .trim_start()
+                .chars()
+                .take_while(|c| !c.is_whitespace())
+                .collect();
+            if tag.is_empty() {
+                break;
+            }
+
+            // 逐行读取正文
+            let mut body = String::new();
+            crossterm::terminal::disable_raw_mode().ok();
+            loop {
+                print!("> ");
+                std::io::stdout().flush().unwrap();
+                let mut line = String::new();
+                loop {
+                    let c = Self::read_char();
+                    if c == u8::from(SpecialKeycode::LF) || c == u8::from(SpecialKeycode::CR) {
+                        println!();
+                        break;
+                    }
+                    line.push(c as char);
+                }
+                if line == tag {
+                    break;
+                }
+                body.push_str(&line);
+                body.push('\n');
+            }
+            crossterm::terminal::enable_raw_mode().ok();
+
+            // 把 `<<TAG` 连同其后的分隔符一起替换为 `<<TAG\0<hex>`
+            let tag_start = op + 2 + (after.len() - after.trim_start().len());
+            let tag_end = tag_start + tag.len();
+            let encoded = format!("<<{}\0{}", tag, hex_encode(body.as_bytes()));
+            result.replace_range(op..tag_end, &encoded);
+            search_from = op + encoded.len();
+        }
+        result
+    }
+
+    /// 处理 `jobs`、`fg %n`、`bg %n` 三个作业控制内建命令。
+    ///
+    /// 返回 `true` 表示该行已被作为内建命令消费，不再走普通执行路径。
+    fn exec_job_builtin(&mut self, input_command: &str) -> bool {
+        let mut args = input_command.split_whitespace();
+        let name = match args.next() {
+            Some(name) => name,
+            None => return false,
+        };
+
+        match name {
+            "jobs" => {
+                for job in self.jobs.iter() {
+                    println!("[{}]\t{}\t{}", job.id, job.state, job.command);
+                }
+                true
+            }
+
+            "fg" | "bg" => {
+                let id = match args.next() {
+                    Some(spec) => spec.trim_start_matches('%').parse::<usize>().ok(),
+                    None => self.jobs.current_id(),
+                };
+
+                let id = match id {
+                    Some(id) => id,
+                    None => {
+                        println!("{}: no such job", name);
+                        return true;
+                    }
+                };
+
+                let shell_pgid = std::process::id() as i32;
+                let (pgid, command) = match self.jobs.get_mut(id) {
+                    Some(job) => {
+                        job.state = JobState::Running;
+                        job.signal(libc::SIGCONT);
+                        (job.pgid, job.command.clone())
+                    }
+                    None => {
+                        println!("{}: {}: no such job", name, id);
+                        return true;
+                    }
+                };
+
+                if name == "fg" {
+                    // 重新夺回终端并在前台等待该作业
+                    crossterm::terminal::disable_raw_mode().expect("failed to disable raw mode");
+                    println!("{}", command);
+                    unsafe { libc::tcsetpgrp(libc::STDIN_FILENO, pgid) };
+
+                    let mut status: i32 = 0;
+                    let mut stopped = false;
+                    loop {
+                        let r = unsafe {
+                            libc::waitpid(-pgid, &mut status as *mut i32, libc::WUNTRACED)
+                        };
+                        if r <= 0 {
+                            break;
+                        }
+                        if unsafe { libc::WIFSTOPPED(status) } {
+                            stopped = true;
+                            break;
+                        }
+                    }
+
+                    unsafe { libc::tcsetpgrp(libc::STDIN_FILENO, shell_pgid) };
+                    crossterm::terminal::enable_raw_mode().expect("failed to enable raw mode");
+
+                    if stopped {
+                        if let Some(job) = self.jobs.get_mut(id) {
+                            job.state = JobState::Stopped;
+                        }
+                        println!("[{}]\tStopped\t{}", id, command);
+                    } else {
+                        self.jobs.remove(id);
+                    }
+                } else {
+                    // bg：保持在后台继续运行
+                    println!("[{}]\t{}&", id, command);
+                }
+                true
+            }
+
+            _ => false,
+        }
+    }
+
     pub fn read_commands(&mut self) {
         let mut history = Vec::new();
         for line in BufReader::new(match File::open(&self.history_path) {
@@ -240,6 +551,133 @@ impl Shell {
         }
     }
 
+    /// Ctrl-W：删除光标左边的一个单词，删掉的文本进 kill-ring。
+    fn erase_word(&mut self) {
+        let killed = self.printer.erase_word();
+        if !killed.is_empty() {
+            self.kill_ring = killed;
+        }
+    }
+
+    /// Ctrl-U：删除从行首到光标之间的内容。
+    fn kill_to_start(&mut self) {
+        let killed = self.printer.kill_to_start();
+        if !killed.is_empty() {
+            self.kill_ring = killed;
+        }
+    }
+
+    /// Ctrl-K：删除从光标到行尾的内容。
+    fn kill_to_end(&mut self) {
+        let killed = self.printer.kill_to_end();
+        if !killed.is_empty() {
+            self.kill_ring = killed;
+        }
+    }
+
+    /// Ctrl-Y：把最近一次删除的文本粘贴回光标处。
+    fn yank(&mut self) {
+        let text = self.kill_ring.clone();
+        self.printer.yank(&text);
+    }
+
+    /// Ctrl-L：清屏并重绘提示符与当前输入，保持光标相对位置不变。
+    fn clear_screen(&mut self) {
+        let buf = self.printer.buf.borrow().clone();
+        let cursor = self.printer.cursor;
+        print!("\x1b[2J\x1b[1;1H");
+        self.printer.print_prompt();
+        print!("{}", String::from_utf8_lossy(&buf));
+        for _ in cursor..buf.len() {
+            print!("\x1b[D");
+        }
+        std::io::stdout().flush().unwrap();
+    }
+
+    /// 在 `before` 之前（不含）从新到旧查找第一条包含 `query` 子串的历史命令。
+    fn rsearch_find(&self, query: &str, before: usize) -> Option<usize> {
+        if query.is_empty() {
+            return None;
+        }
+        (0..before)
+            .rev()
+            .find(|&i| String::from_utf8_lossy(&self.history_commands[i].borrow()).contains(query))
+    }
+
+    /// 重绘 `(reverse-i-search)` 提示行。
+    fn redraw_rsearch(&self, query: &str, matched: &str) {
+        print!("\r\x1b[2K(reverse-i-search)`{}': {}", query, matched);
+        std::io::stdout().flush().unwrap();
+    }
+
+    /// 退出反向搜索：把结果写回 `printer.buf`，清掉搜索提示并重绘普通输入行。
+    fn rsearch_finalize(&mut self, accepted: Vec<u8>) {
+        *self.printer.buf.borrow_mut() = accepted.clone();
+        self.printer.cursor = accepted.len();
+        print!("\r\x1b[2K");
+        self.printer.print_prompt();
+        print!("{}", String::from_utf8_lossy(&accepted));
+        std::io::stdout().flush().unwrap();
+    }
+
+    /// Ctrl-R：反向增量历史搜索。
+    ///
+    /// 进入一个独立的输入循环：边输入边从新到旧扫描 `history_commands` 找到
+    /// 最近的包含当前查询子串的命令并实时显示；再次 Ctrl-R 跳到更旧的匹配，
+    /// 回车把匹配写入 `printer.buf` 供编辑/执行，Esc/Ctrl-G 取消回到原输入行。
+    fn reverse_search(&mut self) {
+        let original = self.printer.buf.borrow().clone();
+        // 最后一条是当前正在编辑的实时缓冲，不参与搜索
+        let pool_len = self.history_commands.len().saturating_sub(1);
+        let mut query = String::new();
+        let mut match_index: Option<usize> = None;
+
+        self.redraw_rsearch(&query, "");
+        loop {
+            let key = Self::read_char();
+            match key {
+                // 再次 Ctrl-R：跳到更旧的匹配
+                18 => {
+                    let before = match_index.unwrap_or(pool_len);
+                    if let Some(n) = self.rsearch_find(&query, before) {
+                        match_index = Some(n);
+                    }
+                }
+                // 回车：接受当前匹配
+                10 | 13 => {
+                    let accepted = match match_index {
+                        Some(m) => self.history_commands[m].borrow().clone(),
+                        None => original.clone(),
+                    };
+                    self.rsearch_finalize(accepted);
+                    return;
+                }
+                // Esc / Ctrl-G：取消，恢复原始输入行
+                27 | 7 => {
+                    self.rsearch_finalize(original.clone());
+                    return;
+                }
+                // 退格：缩短查询并从头重新匹配
+                8 | 127 => {
+                    query.pop();
+                    match_index = self.rsearch_find(&query, pool_len);
+                }
+                1..=31 => {}
+                c => {
+                    query.push(c as char);
+                    let before = match_index.map(|m| m + 1).unwrap_or(pool_len);
+                    match_index = self.rsearch_find(&query, before);
+                }
+            }
+
+            let matched = match match_index {
+                Some(m) => String::from_utf8_lossy(&self.history_commands[m].borrow()).into_owned(),
+                None => String::new(),
+            };
+            self.redraw_rsearch(&query, &matched);
+        }
+    }
+
     fn readline(&mut self) -> usize {
         let mut stdout = std::io::stdout();
         self.history_commands.push(Rc::clone(&self.printer.buf));
@@ -356,6 +794,28 @@ impl Shell {
                 }
             } else {
                 match key {
+                    // Ctrl-C：在提示符处中断意味着放弃当前输入行，而不是结束 shell
+                    3 => {
+                        self.printer.end();
+                        println!();
+                        self.printer.buf.borrow_mut().clear();
+                        self.printer.cursor = 0;
+                        command_index = self.history_commands.len() - 1;
+                        self.printer.print_prompt();
+                    }
+                    // Ctrl-A / Ctrl-E：行首 / 行尾
+                    1 => self.printer.home(),
+                    5 => self.printer.end(),
+                    // Ctrl-K / Ctrl-U / Ctrl-W：向尾 / 向首 / 按词删除
+                    11 => self.kill_to_end(),
+                    21 => self.kill_to_start(),
+                    23 => self.erase_word(),
+                    // Ctrl-Y：粘贴最近删除的文本
+                    25 => self.yank(),
+                    // Ctrl-L：清屏重绘
+                    12 => self.clear_screen(),
+                    // Ctrl-R：反向增量历史搜索
+                    18 => self.reverse_search(),
                     1..=31 => {}
                     c => {
                         self.printer.insert(&[c]);
@@ -403,9 +863,88 @@ pub fn complete_command(command: &str) -> (&str, Vec<String>) {
             candidates.push(String::from(cmd));
         }
     }
+
+    // 除内建命令外，还要从 PATH 的每个目录里补全可执行文件
+    for dir in path_dirs() {
+        if let Ok(read_dir) = fs::read_dir(&dir) {
+            for entry in read_dir.flatten() {
+                let file_name = entry.file_name().into_string().unwrap_or_default();
+                if !file_name.starts_with(command) {
+                    continue;
+                }
+                let full = format!("{}/{}", dir.trim_end_matches('/'), file_name);
+                // 过滤掉没有任何执行权限位、或当前用户无法执行的文件
+                if is_executable(&full) && !candidates.contains(&file_name) {
+                    candidates.push(file_name);
+                }
+            }
+        }
+    }
+
     ("", candidates)
 }
 
+/// 按 shell 惯例拆分 `PATH`：以 `:` 分隔，空段视作当前目录。
+fn path_dirs() -> Vec<String> {
+    EnvManager::get("PATH")
+        .unwrap_or_default()
+        .split(':')
+        .map(|dir| {
+            if dir.is_empty() {
+                ".".to_string()
+            } else {
+                dir.to_string()
+            }
+        })
+        .collect()
+}
+
+/// 判断 `path` 是否是一个当前用户可执行的普通文件。
+///
+/// 先看 `S_IXUSR`/`S_IXGRP`/`S_IXOTH` 中是否有执行位，再用 `access(X_OK)`
+/// 确认在当前有效用户/组下确实可执行。
+fn is_executable(path: &str) -> bool {
+    let c_path = match std::ffi::CString::new(path) {
+        Ok(c) => c,
+        Err(_) => return false,
+    };
+    let mut st: libc::stat = unsafe { std::mem::zeroed() };
+    if unsafe { libc::stat(c_path.as_ptr(), &mut st) } != 0 {
+        return false;
+    }
+    let is_regular = (st.st_mode & libc::S_IFMT) == libc::S_IFREG;
+    let exec_bits = libc::S_IXUSR | libc::S_IXGRP | libc::S_IXOTH;
+    is_regular
+        && (st.st_mode & exec_bits) != 0
+        && unsafe { libc::access(c_path.as_ptr(), libc::X_OK) } == 0
+}
+
+/// 在 `PATH` 中解析一个裸命令名，返回第一个可执行文件的绝对/相对路径。
+///
+/// 命令本身已含 `/` 时不做查找，可执行则原样返回，否则返回 `None`；供流水线执行路径把裸命令名
+/// 解析成真正能 `exec` 的文件。
+pub fn search_in_path(command: &str) -> Option<String> {
+    if command.contains('/') {
+        return is_executable(command).then(|| command.to_string());
+    }
+    for dir in path_dirs() {
+        let full = format!("{}/{}", dir.trim_end_matches('/'), command);
+        if is_executable(&full) {
+            return Some(full);
+        }
+    }
+    None
+}
+
+/// 把字节串编码成十六进制字符串，用于把 here-doc 正文安全地塞进一个 token。
+fn hex_encode(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        s.push_str(&format!("{:02x}", b));
+    }
+    s
+}
+
 pub fn complete_path(incomplete_path: &str) -> (&str, Vec<String>) {
     let mut candidates: Vec<String> = Vec::new();
     let mut dir = "";